@@ -0,0 +1,123 @@
+use std::{env, process::Command};
+
+use anyhow::{bail, Result};
+
+/// The container tooling used to build and run images.
+///
+/// Defaults to whatever is found on `$PATH` (preferring `docker`), but can be
+/// pinned explicitly via the `--engine` flag or the `NIXPACKS_ENGINE` env var
+/// so rootless/daemonless setups (Podman) work without symlinking `docker`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerEngine {
+    Docker,
+    Podman,
+}
+
+impl ContainerEngine {
+    /// Resolve the engine to use: an explicit `--engine` flag wins, then
+    /// `NIXPACKS_ENGINE`, then auto-detection from `$PATH`.
+    pub fn resolve(explicit: Option<&str>) -> Result<ContainerEngine> {
+        if let Some(name) = explicit {
+            return ContainerEngine::parse(name);
+        }
+
+        if let std::result::Result::Ok(name) = env::var("NIXPACKS_ENGINE") {
+            return ContainerEngine::parse(&name);
+        }
+
+        ContainerEngine::detect()
+    }
+
+    fn parse(name: &str) -> Result<ContainerEngine> {
+        match name.to_lowercase().as_str() {
+            "docker" => std::result::Result::Ok(ContainerEngine::Docker),
+            "podman" => std::result::Result::Ok(ContainerEngine::Podman),
+            other => bail!("Unknown container engine '{}', expected docker or podman", other),
+        }
+    }
+
+    /// Probe `$PATH` for a working engine binary, preferring `docker`.
+    fn detect() -> Result<ContainerEngine> {
+        if ContainerEngine::Docker.is_available() {
+            return std::result::Result::Ok(ContainerEngine::Docker);
+        }
+
+        if ContainerEngine::Podman.is_available() {
+            return std::result::Result::Ok(ContainerEngine::Podman);
+        }
+
+        bail!("Could not find docker or podman on PATH")
+    }
+
+    fn is_available(&self) -> bool {
+        Command::new(self.binary())
+            .arg("--version")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+
+    pub fn binary(&self) -> &'static str {
+        match self {
+            ContainerEngine::Docker => "docker",
+            ContainerEngine::Podman => "podman",
+        }
+    }
+
+    /// The env var this engine reads to target a remote daemon.
+    pub fn host_env_var(&self) -> &'static str {
+        match self {
+            ContainerEngine::Docker => "DOCKER_HOST",
+            ContainerEngine::Podman => "CONTAINER_HOST",
+        }
+    }
+
+    /// The remote host configured for this engine, if any. Only this
+    /// engine's own env var is consulted (`DOCKER_HOST` for Docker,
+    /// `CONTAINER_HOST` for Podman) — falling back to the other engine's
+    /// var would point the build at the wrong daemon.
+    pub fn remote_host(&self) -> Option<String> {
+        env::var(self.host_env_var()).ok()
+    }
+
+    /// Build a `Command` for this engine, pre-wired with the remote host env
+    /// var (if configured) so it targets the right daemon.
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new(self.binary());
+
+        if let Some(host) = self.remote_host() {
+            cmd.env(self.host_env_var(), host);
+        }
+
+        cmd
+    }
+}
+
+impl std::fmt::Display for ContainerEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.binary())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_accepts_known_engines_case_insensitively() {
+        assert_eq!(ContainerEngine::parse("docker").unwrap(), ContainerEngine::Docker);
+        assert_eq!(ContainerEngine::parse("Podman").unwrap(), ContainerEngine::Podman);
+        assert_eq!(ContainerEngine::parse("PODMAN").unwrap(), ContainerEngine::Podman);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_engines() {
+        assert!(ContainerEngine::parse("nerdctl").is_err());
+    }
+
+    #[test]
+    fn host_env_var_is_specific_to_each_engine() {
+        assert_eq!(ContainerEngine::Docker.host_env_var(), "DOCKER_HOST");
+        assert_eq!(ContainerEngine::Podman.host_env_var(), "CONTAINER_HOST");
+    }
+}
@@ -0,0 +1,34 @@
+use std::process::ExitStatus;
+
+use thiserror::Error;
+
+/// Errors raised while detecting, generating, or building a project, kept
+/// distinct from the `anyhow::Error` wrapping so callers (namely the CLI)
+/// can recover the exit code of a failed subprocess instead of always
+/// exiting 1.
+#[derive(Debug, Error)]
+pub enum NixpacksError {
+    #[error("Failed to match a builder")]
+    NoBuilderFound,
+
+    #[error("Command `{command} {}` failed with {status}", args.join(" "))]
+    CommandFailed {
+        command: String,
+        args: Vec<String>,
+        status: ExitStatus,
+    },
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl NixpacksError {
+    /// The process exit code the CLI should forward, mirroring the failing
+    /// subprocess's own code where one is available.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            NixpacksError::CommandFailed { status, .. } => status.code().unwrap_or(1),
+            _ => 1,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use glob::Pattern;
+
+/// Paths nixpacks always excludes from the build context, regardless of
+/// what a project's ignore files say.
+///
+/// `tmp` is nixpacks' own output directory (see `./tmp/<uuid>` in
+/// `AppBuilder::build`), not a generic match against any directory named
+/// `tmp` in the project — a bare component match would also drop
+/// legitimate project directories like Rails' `tmp/`.
+const ALWAYS_EXCLUDED: &[&str] = &[".git", "./tmp"];
+
+/// Read exclude globs from `.dockerignore` and `.nixpacksignore` at the
+/// root of `source`, plus the paths nixpacks always excludes.
+pub fn load_excludes(source: &Path) -> Result<Vec<String>> {
+    let mut excludes: Vec<String> = ALWAYS_EXCLUDED.iter().map(|s| s.to_string()).collect();
+
+    for file_name in [".dockerignore", ".nixpacksignore"] {
+        let path = source.join(file_name);
+        if !path.exists() {
+            continue;
+        }
+
+        let contents = fs::read_to_string(&path).with_context(|| format!("Reading {}", path.display()))?;
+        excludes.extend(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+
+    Ok(excludes)
+}
+
+fn is_excluded(relative_path: &Path, excludes: &[String]) -> bool {
+    let relative_str = relative_path.to_string_lossy();
+
+    excludes.iter().any(|pattern| {
+        // A leading "./" anchors the pattern to the source root, matching
+        // only that top-level path rather than a component at any depth
+        // (used for nixpacks' own `./tmp` output dir, so a project's own
+        // `tmp/` elsewhere in the tree is left alone).
+        if let Some(anchored) = pattern.strip_prefix("./") {
+            return relative_path
+                .components()
+                .next()
+                .map(|first| first.as_os_str() == anchored)
+                .unwrap_or(false);
+        }
+
+        relative_path
+            .components()
+            .any(|component| component.as_os_str() == pattern.as_str())
+            || Pattern::new(pattern)
+                .map(|glob| glob.matches(&relative_str))
+                .unwrap_or(false)
+    })
+}
+
+/// Recursively collect every file under `source` that survives `excludes`.
+/// Directories are descended into but not returned themselves.
+pub fn walk(source: &Path, excludes: &[String]) -> Result<Vec<PathBuf>> {
+    let mut surviving = Vec::new();
+    walk_into(source, source, excludes, &mut surviving)?;
+    Ok(surviving)
+}
+
+fn walk_into(root: &Path, dir: &Path, excludes: &[String], surviving: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Reading directory {}", dir.display()))? {
+        let entry = entry?;
+        let path = entry.path();
+        let relative = path
+            .strip_prefix(root)
+            .expect("walked path should be under root");
+
+        if is_excluded(relative, excludes) {
+            continue;
+        }
+
+        if path.is_dir() {
+            walk_into(root, &path, excludes, surviving)?;
+        } else {
+            surviving.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Copy every surviving path from `source` into `dest`, recreating the
+/// directory structure as needed.
+pub fn copy_filtered(source: &Path, dest: &Path, excludes: &[String]) -> Result<()> {
+    fs::create_dir_all(dest).with_context(|| format!("Creating {}", dest.display()))?;
+
+    for path in walk(source, excludes)? {
+        let relative = path.strip_prefix(source).expect("walked path should be under root");
+        let target = dest.join(relative);
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Creating {}", parent.display()))?;
+        }
+
+        fs::copy(&path, &target)
+            .with_context(|| format!("Copying {} to {}", path.display(), target.display()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_level_tmp_is_excluded() {
+        let excludes = vec!["./tmp".to_string()];
+        assert!(is_excluded(Path::new("tmp"), &excludes));
+        assert!(is_excluded(Path::new("tmp/abc/environment.nix"), &excludes));
+    }
+
+    #[test]
+    fn nested_tmp_directories_are_not_excluded() {
+        let excludes = vec!["./tmp".to_string()];
+        assert!(!is_excluded(Path::new("app/tmp"), &excludes));
+        assert!(!is_excluded(Path::new("app/tmp/cache.txt"), &excludes));
+    }
+
+    #[test]
+    fn dot_git_is_excluded_at_any_depth() {
+        let excludes = vec![".git".to_string()];
+        assert!(is_excluded(Path::new(".git"), &excludes));
+        assert!(is_excluded(Path::new("vendor/submodule/.git"), &excludes));
+    }
+
+    #[test]
+    fn glob_patterns_from_ignore_files_still_match() {
+        let excludes = vec!["*.log".to_string()];
+        assert!(is_excluded(Path::new("debug.log"), &excludes));
+        assert!(!is_excluded(Path::new("main.rs"), &excludes));
+    }
+}
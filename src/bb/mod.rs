@@ -1,22 +1,41 @@
-use anyhow::{bail, Context, Ok, Result};
-use indoc::formatdoc;
+use anyhow::{Context, Ok, Result};
 use std::{
     fs::{self, File},
     io::Write,
-    path::PathBuf,
-    process::Command,
+    path::{Path, PathBuf},
 };
 use uuid::Uuid;
 
 use crate::builders::Builder;
 
+mod engine;
+pub mod error;
+mod ignore;
+mod nixpkgs;
+mod process;
+pub mod remote;
+mod template;
+mod watch;
+pub use engine::ContainerEngine;
+pub use error::NixpacksError;
+pub use nixpkgs::NixPin;
+use process::run_command;
+
 #[derive(Debug, Clone)]
 pub struct AppSource {
     pub source: PathBuf,
+    // Consulted by language builders (not part of this slice of the crate)
+    // via `includes_file` to decide whether they match an app.
+    #[allow(dead_code)]
     pub paths: Vec<PathBuf>,
+    /// Glob patterns from `.dockerignore`/`.nixpacksignore` (plus nixpacks'
+    /// own always-excluded paths) that builders can also consult during
+    /// detection.
+    pub excludes: Vec<String>,
 }
 
 impl AppSource {
+    #[allow(dead_code)]
     pub fn includes_file(&self, name: &str) -> bool {
         for path in &self.paths {
             if path.file_name().unwrap() == name {
@@ -28,6 +47,20 @@ impl AppSource {
     }
 }
 
+/// Optional configuration for [`AppBuilder::new`], grouped into one struct
+/// so adding a new knob (engine, pin, push target, ...) doesn't grow the
+/// constructor's argument list.
+#[derive(Default)]
+pub struct AppBuilderOptions {
+    pub custom_build_cmd: Option<String>,
+    pub custom_start_cmd: Option<String>,
+    pub pkgs: Vec<String>,
+    pub engine: Option<String>,
+    pub entrypoint: bool,
+    pub push: Option<String>,
+    pub nixpkgs_pin: Option<NixPin>,
+}
+
 pub struct AppBuilder<'a> {
     name: Option<String>,
     app: AppSource,
@@ -35,27 +68,46 @@ pub struct AppBuilder<'a> {
     custom_start_cmd: Option<String>,
     pkgs: Vec<String>,
     builder: Option<&'a dyn Builder>,
+    engine: ContainerEngine,
+    entrypoint: bool,
+    push: Option<String>,
+    nixpkgs_pin: Option<NixPin>,
 }
 
 impl<'a> AppBuilder<'a> {
     pub fn new(
         name: Option<String>,
         source: PathBuf,
-        custom_build_cmd: Option<String>,
-        custom_start_cmd: Option<String>,
-        pkgs: Vec<String>,
+        options: AppBuilderOptions,
     ) -> Result<AppBuilder<'a>> {
         let dir = fs::read_dir(source.clone()).context("Failed to read app source directory")?;
 
         let paths: Vec<PathBuf> = dir.map(|path| path.unwrap().path()).collect();
 
+        let excludes = ignore::load_excludes(&source)?;
+
+        let engine = ContainerEngine::resolve(options.engine.as_deref())?;
+
+        let nixpkgs_pin = match options.nixpkgs_pin {
+            Some(pin) => Some(pin),
+            None => nixpkgs::load_pin_from_config(&source)?,
+        };
+
         Ok(AppBuilder {
             name,
-            app: AppSource { source, paths },
-            custom_build_cmd,
-            custom_start_cmd,
-            pkgs,
+            app: AppSource {
+                source,
+                paths,
+                excludes,
+            },
+            custom_build_cmd: options.custom_build_cmd,
+            custom_start_cmd: options.custom_start_cmd,
+            pkgs: options.pkgs,
             builder: None,
+            engine,
+            entrypoint: options.entrypoint,
+            push: options.push,
+            nixpkgs_pin,
         })
     }
 
@@ -75,7 +127,7 @@ impl<'a> AppBuilder<'a> {
             None => {
                 // If no builder is found, only fail if there is no start command
                 if self.custom_start_cmd.is_none() {
-                    bail!("Failed to match a builder")
+                    return Err(NixpacksError::NoBuilderFound.into());
                 }
 
                 println!("  -> No builders matched")
@@ -85,6 +137,17 @@ impl<'a> AppBuilder<'a> {
         Ok(())
     }
 
+    /// Re-run detect -> gen_nix -> gen_dockerfile -> build every time a file
+    /// under `self.app.source` changes, notifying the desktop on completion.
+    pub fn watch(&mut self, builders: Vec<&'a dyn Builder>) -> Result<()> {
+        let source = self.app.source.clone();
+
+        watch::watch(&source, || {
+            self.detect(builders.clone())?;
+            self.build()
+        })
+    }
+
     pub fn build(&self) -> Result<()> {
         println!("\n=== Building ===");
 
@@ -94,18 +157,23 @@ impl<'a> AppBuilder<'a> {
         let dockerfile = self.gen_dockerfile()?;
         println!("  -> Generated Dockerfile");
 
+        let name = self.name.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        if remote::is_enabled() && self.engine.remote_host().is_some() {
+            return self.build_remote(&nix_expression, &dockerfile, &name);
+        }
+
         let id = Uuid::new_v4();
         let tmp_dir_name = format!("./tmp/{}", id);
 
         println!("  -> Copying source to tmp dir");
 
-        let source = self.app.source.as_path().to_str().unwrap();
-        let mut copy_cmd = Command::new("cp")
-            .arg("-R")
-            .arg(source)
-            .arg(tmp_dir_name.clone())
-            .spawn()?;
-        copy_cmd.wait().context("Copying app source to tmp dir")?;
+        ignore::copy_filtered(
+            &self.app.source,
+            Path::new(tmp_dir_name.as_str()),
+            &self.app.excludes,
+        )
+        .context("Copying app source to tmp dir")?;
 
         println!("  -> Writing environment.nix");
 
@@ -129,20 +197,43 @@ impl<'a> AppBuilder<'a> {
 
         println!("  -> Building image");
 
-        let name = self.name.clone().unwrap_or_else(|| id.to_string());
-
-        let mut docker_build_cmd = Command::new("docker")
+        let mut engine_build_cmd = self.engine.command();
+        engine_build_cmd
             .arg("build")
             .arg(tmp_dir_name.as_str())
             .arg("-t")
-            .arg(name.clone())
-            .spawn()?;
+            .arg(name.clone());
+        run_command(engine_build_cmd)?;
+
+        println!("  -> Built!");
+
+        self.push_if_configured(&name)?;
 
-        docker_build_cmd.wait().context("Building image")?;
+        println!("\nRun:\n  {} run {}", self.engine, name);
+
+        Ok(())
+    }
+
+    /// Build against a remote daemon by shipping the source and generated
+    /// files into a data volume instead of assuming a shared filesystem.
+    fn build_remote(&self, nix_expression: &str, dockerfile: &str, name: &str) -> Result<()> {
+        println!("  -> Building remotely via data volume");
+
+        let volume = remote::VolumeGuard::create(&self.engine)?;
+        println!("  -> Created data volume {}", volume.name);
+
+        volume.populate(&self.app.source, nix_expression, dockerfile)?;
+        println!("  -> Copied source into data volume");
+
+        println!("  -> Building image");
+
+        volume.build(name)?;
 
         println!("  -> Built!");
 
-        println!("\nRun:\n  docker run {}", name);
+        self.push_if_configured(name)?;
+
+        println!("\nRun:\n  {} run {}", self.engine, name);
 
         Ok(())
     }
@@ -170,21 +261,15 @@ impl<'a> AppBuilder<'a> {
             None => user_pkgs,
         };
 
-        // let nix_expression = formatdoc! {"
-        //   {{ pkgs ? import <nixpkgs> {{ }} }}:
-
-        //   pkgs.mkShell {{
-        //     buildInputs = [ {pkgs} ];
-        //   }}
-        // ",
-        // pkgs=pkgs};
-
-        let nix_expression = formatdoc! {"
-          with import <nixpkgs> {{ }}; [ {pkgs} ]
-        ",
-        pkgs=pkgs};
-
-        Ok(nix_expression)
+        template::render_nix(
+            &self.app.source,
+            &template::NixContext {
+                pkgs,
+                pinned: self.nixpkgs_pin.is_some(),
+                nixpkgs_url: self.nixpkgs_pin.as_ref().map(NixPin::tarball_url),
+                sha256: self.nixpkgs_pin.as_ref().map(|pin| pin.sha256.clone()),
+            },
+        )
     }
 
     pub fn gen_dockerfile(&self) -> Result<String> {
@@ -213,30 +298,38 @@ impl<'a> AppBuilder<'a> {
         };
         let start_cmd = self.custom_start_cmd.clone().unwrap_or(suggested_start_cmd);
 
-        let dockerfile = formatdoc! {"
-          FROM nixos/nix
-
-          RUN nix-channel --update
+        template::render_dockerfile(
+            &self.app.source,
+            &template::DockerfileContext {
+                base_image: "nixos/nix".to_string(),
+                install_cmd,
+                build_cmd,
+                start_cmd,
+                entrypoint: self.entrypoint,
+                pinned: self.nixpkgs_pin.is_some(),
+            },
+        )
+    }
 
-          COPY . /app
-          WORKDIR /app
+    /// Tag and push the built image to `registry/repo:tag` when `--push`
+    /// was given.
+    fn push_if_configured(&self, name: &str) -> Result<()> {
+        let Some(destination) = &self.push else {
+            return Ok(());
+        };
 
-          # Load Nix environment
-          RUN nix-env -if environment.nix
+        println!("  -> Pushing to {}", destination);
 
-          # Install
-          RUN {install_cmd}
+        let mut tag_cmd = self.engine.command();
+        tag_cmd.arg("tag").arg(name).arg(destination);
+        run_command(tag_cmd)?;
 
-          # Build
-          RUN {build_cmd}
+        let mut push_cmd = self.engine.command();
+        push_cmd.arg("push").arg(destination);
+        run_command(push_cmd)?;
 
-          # Start
-          CMD {start_cmd}
-        ",
-        install_cmd=install_cmd,
-        build_cmd=build_cmd,
-        start_cmd=start_cmd};
+        println!("  -> Pushed!");
 
-        Ok(dockerfile)
+        Ok(())
     }
 }
\ No newline at end of file
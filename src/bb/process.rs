@@ -0,0 +1,28 @@
+use std::process::Command;
+
+use anyhow::Result;
+
+use crate::bb::error::NixpacksError;
+
+/// Spawn `command`, wait for it, and turn a non-zero exit into a
+/// `NixpacksError::CommandFailed` instead of silently returning `Ok(())`.
+pub fn run_command(mut command: Command) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let args = command
+        .get_args()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect::<Vec<String>>();
+
+    let status = command.spawn().map_err(NixpacksError::Io)?.wait().map_err(NixpacksError::Io)?;
+
+    if !status.success() {
+        return Err(NixpacksError::CommandFailed {
+            command: program,
+            args,
+            status,
+        }
+        .into());
+    }
+
+    Ok(())
+}
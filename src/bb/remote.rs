@@ -0,0 +1,252 @@
+use std::{
+    env,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+use uuid::Uuid;
+
+use crate::bb::{error::NixpacksError, process::run_command, ContainerEngine};
+
+/// Whether remote-build mode is enabled via `NIXPACKS_REMOTE=true`.
+///
+/// Remote mode assumes the selected engine's daemon does not share a
+/// filesystem with this process, so the build context is shipped into a
+/// named volume instead of relying on a local bind mount.
+pub fn is_enabled() -> bool {
+    env::var("NIXPACKS_REMOTE")
+        .map(|value| value == "true")
+        .unwrap_or(false)
+}
+
+/// A data volume created for a single remote build, removed on drop so a
+/// crash or early return never leaks state on the remote daemon.
+pub struct VolumeGuard<'a> {
+    engine: &'a ContainerEngine,
+    pub name: String,
+}
+
+impl<'a> VolumeGuard<'a> {
+    /// Create a fresh, uniquely-named volume for this build.
+    pub fn create(engine: &'a ContainerEngine) -> Result<VolumeGuard<'a>> {
+        let name = format!("nixpacks-{}", Uuid::new_v4());
+
+        let mut create_cmd = engine.command();
+        create_cmd.arg("volume").arg("create").arg(&name);
+        run_command(create_cmd)?;
+
+        Ok(VolumeGuard { engine, name })
+    }
+
+    /// Copy the app source plus generated `environment.nix`/`Dockerfile`
+    /// into the volume. The source is never bind-mounted from this machine:
+    /// it's streamed as a tar archive over stdin into a
+    /// short-lived helper container that extracts it into the volume, so
+    /// this works even when `source` only exists on this machine.
+    pub fn populate(
+        &self,
+        source: &Path,
+        nix_expression: &str,
+        dockerfile: &str,
+    ) -> Result<()> {
+        stream_tar_into_volume(self.engine, &self.name, source)?;
+
+        write_into_volume(self.engine, &self.name, "environment.nix", nix_expression)?;
+        write_into_volume(self.engine, &self.name, "Dockerfile", dockerfile)?;
+
+        Ok(())
+    }
+
+    /// Build the image from this volume's contents by streaming a tar of
+    /// the volume straight into `engine build -`, so the remote daemon
+    /// never needs a local bind mount for the build context either.
+    pub fn build(&self, name: &str) -> Result<()> {
+        let mut tar_cmd = self.engine.command();
+        tar_cmd
+            .arg("run")
+            .arg("--rm")
+            .arg("-v")
+            .arg(format!("{}:/data", self.name))
+            .arg("busybox")
+            .arg("tar")
+            .arg("-cf")
+            .arg("-")
+            .arg("-C")
+            .arg("/data")
+            .arg(".")
+            .stdout(Stdio::piped());
+
+        let mut tar_child = tar_cmd.spawn().map_err(NixpacksError::Io)?;
+        let tar_stdout = tar_child
+            .stdout
+            .take()
+            .context("Failed to open stdout for volume tar stream")?;
+
+        let mut build_cmd = self.engine.command();
+        build_cmd
+            .arg("build")
+            .arg("-t")
+            .arg(name)
+            .arg("-")
+            .stdin(Stdio::from(tar_stdout));
+        run_command(build_cmd)?;
+
+        let status = tar_child.wait().map_err(NixpacksError::Io)?;
+        if !status.success() {
+            return Err(NixpacksError::CommandFailed {
+                command: "tar".to_string(),
+                args: vec!["-cf".to_string(), "-".to_string(), "-C".to_string(), "/data".to_string(), ".".to_string()],
+                status,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+fn stream_tar_into_volume(engine: &ContainerEngine, volume: &str, source: &Path) -> Result<()> {
+    let mut tar_cmd = Command::new("tar");
+    tar_cmd
+        .arg("-cf")
+        .arg("-")
+        .arg("-C")
+        .arg(source)
+        .arg(".")
+        .stdout(Stdio::piped());
+
+    let mut tar_child = tar_cmd.spawn().map_err(NixpacksError::Io)?;
+    let tar_stdout = tar_child
+        .stdout
+        .take()
+        .context("Failed to open stdout for local source tar stream")?;
+
+    let mut extract_cmd = engine.command();
+    extract_cmd
+        .arg("run")
+        .arg("--rm")
+        .arg("-i")
+        .arg("-v")
+        .arg(format!("{}:/data", volume))
+        .arg("busybox")
+        .arg("tar")
+        .arg("-xf")
+        .arg("-")
+        .arg("-C")
+        .arg("/data")
+        .stdin(Stdio::from(tar_stdout));
+    run_command(extract_cmd)?;
+
+    let status = tar_child.wait().map_err(NixpacksError::Io)?;
+    if !status.success() {
+        return Err(NixpacksError::CommandFailed {
+            command: "tar".to_string(),
+            args: vec!["-cf".to_string(), "-".to_string(), "-C".to_string(), source.display().to_string(), ".".to_string()],
+            status,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+impl<'a> Drop for VolumeGuard<'a> {
+    fn drop(&mut self) {
+        let result = self
+            .engine
+            .command()
+            .arg("volume")
+            .arg("rm")
+            .arg("-f")
+            .arg(&self.name)
+            .status();
+
+        if let Err(err) = result {
+            eprintln!("Failed to remove data volume '{}': {}", self.name, err);
+        }
+    }
+}
+
+fn write_into_volume(engine: &ContainerEngine, volume: &str, file_name: &str, contents: &str) -> Result<()> {
+    let mut command = engine.command();
+    command
+        .arg("run")
+        .arg("--rm")
+        .arg("-i")
+        .arg("-v")
+        .arg(format!("{}:/data", volume))
+        .arg("busybox")
+        .arg("sh")
+        .arg("-c")
+        .arg(format!("cat > /data/{}", file_name))
+        .stdin(std::process::Stdio::piped());
+
+    let program = command.get_program().to_string_lossy().to_string();
+    let args = command
+        .get_args()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect::<Vec<String>>();
+
+    let mut child = command.spawn().map_err(NixpacksError::Io)?;
+
+    {
+        use std::io::Write;
+        let stdin = child
+            .stdin
+            .as_mut()
+            .context("Failed to open stdin for helper container")?;
+        stdin.write_all(contents.as_bytes())?;
+    }
+
+    let status = child.wait().map_err(NixpacksError::Io)?;
+    if !status.success() {
+        return Err(NixpacksError::CommandFailed {
+            command: program,
+            args,
+            status,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// List volumes created by nixpacks (`cross-util`-style maintenance command).
+pub fn list_volumes(engine: &ContainerEngine) -> Result<Vec<String>> {
+    let output = engine
+        .command()
+        .arg("volume")
+        .arg("ls")
+        .arg("--filter")
+        .arg("name=nixpacks-")
+        .arg("--format")
+        .arg("{{.Name}}")
+        .output()
+        .context("Listing nixpacks data volumes")?;
+
+    let names = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+
+    Ok(names)
+}
+
+/// Remove the given volumes by name.
+pub fn remove_volumes(engine: &ContainerEngine, names: &[String]) -> Result<()> {
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    let mut remove_cmd = engine.command();
+    remove_cmd.arg("volume").arg("rm").args(names);
+    run_command(remove_cmd)
+}
+
+/// Remove every nixpacks-created volume. Volumes still in use by a container
+/// are not forced out: the engine refuses to remove them and this fails.
+pub fn prune_volumes(engine: &ContainerEngine) -> Result<()> {
+    let volumes = list_volumes(engine)?;
+    remove_volumes(engine, &volumes)
+}
@@ -0,0 +1,166 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use handlebars::Handlebars;
+use serde::Serialize;
+
+/// Built-in Dockerfile template, used unless the project ships its own
+/// `Dockerfile.hbs` at the source root.
+const DEFAULT_DOCKERFILE_TEMPLATE: &str = r#"FROM {{base_image}}
+
+{{#unless pinned}}RUN nix-channel --update
+{{/unless}}
+COPY . /app
+WORKDIR /app
+
+# Load Nix environment
+RUN nix-env -if environment.nix
+
+# Install
+RUN {{install_cmd}}
+
+# Build
+RUN {{build_cmd}}
+
+# Start
+{{#if entrypoint}}ENTRYPOINT {{start_cmd}}{{else}}CMD {{start_cmd}}{{/if}}
+"#;
+
+/// Built-in Nix expression template, used unless the project ships its own
+/// `environment.nix.hbs` at the source root. Imports a pinned nixpkgs
+/// revision via `fetchTarball` when one is configured, falling back to the
+/// base image's channel otherwise.
+const DEFAULT_NIX_TEMPLATE: &str = r#"{{#if pinned}}with import (fetchTarball { url = "{{nixpkgs_url}}"; sha256 = "{{sha256}}"; }) { }; [ {{pkgs}} ]
+{{else}}with import <nixpkgs> { }; [ {{pkgs}} ]
+{{/if}}"#;
+
+#[derive(Serialize)]
+pub struct DockerfileContext {
+    pub base_image: String,
+    pub install_cmd: String,
+    pub build_cmd: String,
+    pub start_cmd: String,
+    pub entrypoint: bool,
+    pub pinned: bool,
+}
+
+#[derive(Serialize)]
+pub struct NixContext {
+    pub pkgs: String,
+    pub pinned: bool,
+    pub nixpkgs_url: Option<String>,
+    pub sha256: Option<String>,
+}
+
+/// Render the Dockerfile, preferring a user-supplied `Dockerfile.hbs` in
+/// `source` over the built-in template.
+pub fn render_dockerfile(source: &Path, context: &DockerfileContext) -> Result<String> {
+    render(source, "Dockerfile.hbs", DEFAULT_DOCKERFILE_TEMPLATE, context)
+}
+
+/// Render `environment.nix`, preferring a user-supplied
+/// `environment.nix.hbs` in `source` over the built-in template.
+pub fn render_nix(source: &Path, context: &NixContext) -> Result<String> {
+    render(source, "environment.nix.hbs", DEFAULT_NIX_TEMPLATE, context)
+}
+
+fn render<T: Serialize>(source: &Path, override_name: &str, default_template: &str, context: &T) -> Result<String> {
+    let override_path = source.join(override_name);
+
+    let template = if override_path.exists() {
+        std::fs::read_to_string(&override_path)
+            .with_context(|| format!("Reading override template {}", override_path.display()))?
+    } else {
+        default_template.to_string()
+    };
+
+    let mut handlebars = Handlebars::new();
+    handlebars.set_strict_mode(true);
+    // Shell commands and Nix expressions are not HTML — the default escaper
+    // would mangle quotes/ampersands in install/build/start commands.
+    handlebars.register_escape_fn(handlebars::no_escape);
+
+    handlebars
+        .render_template(&template, context)
+        .with_context(|| format!("Rendering template {}", override_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn dockerfile_context() -> DockerfileContext {
+        DockerfileContext {
+            base_image: "nixos/nix".to_string(),
+            install_cmd: "npm install".to_string(),
+            build_cmd: "npm run build".to_string(),
+            start_cmd: r#"["npm", "start"]"#.to_string(),
+            entrypoint: false,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn dockerfile_uses_cmd_by_default() {
+        let rendered = render_dockerfile(Path::new("/nonexistent-nixpacks-source"), &dockerfile_context()).unwrap();
+        assert!(rendered.contains(r#"CMD ["npm", "start"]"#));
+        assert!(!rendered.contains("ENTRYPOINT"));
+    }
+
+    #[test]
+    fn dockerfile_uses_entrypoint_when_configured() {
+        let mut context = dockerfile_context();
+        context.entrypoint = true;
+        let rendered = render_dockerfile(Path::new("/nonexistent-nixpacks-source"), &context).unwrap();
+        assert!(rendered.contains(r#"ENTRYPOINT ["npm", "start"]"#));
+    }
+
+    #[test]
+    fn dockerfile_does_not_html_escape_shell_commands() {
+        let mut context = dockerfile_context();
+        context.install_cmd = "echo \"a\" && echo 'b'".to_string();
+        let rendered = render_dockerfile(Path::new("/nonexistent-nixpacks-source"), &context).unwrap();
+        assert!(rendered.contains("echo \"a\" && echo 'b'"));
+    }
+
+    #[test]
+    fn nix_expression_imports_pinned_tarball_when_configured() {
+        let context = NixContext {
+            pkgs: "nodejs".to_string(),
+            pinned: true,
+            nixpkgs_url: Some("https://example.com/nixpkgs.tar.gz".to_string()),
+            sha256: Some("deadbeef".to_string()),
+        };
+        let rendered = render_nix(Path::new("/nonexistent-nixpacks-source"), &context).unwrap();
+        assert!(rendered.contains("fetchTarball"));
+        assert!(rendered.contains("https://example.com/nixpkgs.tar.gz"));
+        assert!(!rendered.contains("<nixpkgs>"));
+    }
+
+    #[test]
+    fn nix_expression_falls_back_to_channel_when_unpinned() {
+        let context = NixContext {
+            pkgs: "nodejs".to_string(),
+            pinned: false,
+            nixpkgs_url: None,
+            sha256: None,
+        };
+        let rendered = render_nix(Path::new("/nonexistent-nixpacks-source"), &context).unwrap();
+        assert!(rendered.contains("<nixpkgs>"));
+        assert!(!rendered.contains("fetchTarball"));
+    }
+
+    #[test]
+    fn user_supplied_override_template_wins() {
+        let dir = std::env::temp_dir().join(format!("nixpacks-template-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Dockerfile.hbs"), "FROM scratch\n").unwrap();
+
+        let rendered = render_dockerfile(&dir, &dockerfile_context()).unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(rendered, "FROM scratch\n");
+    }
+}
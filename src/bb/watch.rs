@@ -0,0 +1,123 @@
+use std::{
+    path::Path,
+    sync::mpsc::{channel, RecvTimeoutError},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use notify_rust::Notification;
+
+/// How long to wait for more filesystem events before kicking off a rebuild.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Watch `source` for changes and call `rebuild` on every debounced batch of
+/// events, skipping the tool's own `./tmp` output dir and `.git`.
+///
+/// `rebuild` is expected to run the detect -> gen_nix -> gen_dockerfile ->
+/// build pipeline and report success/failure; a desktop notification is sent
+/// after each attempt so this is usable as a background dev-loop.
+pub fn watch<F>(source: &Path, mut rebuild: F) -> Result<()>
+where
+    F: FnMut() -> Result<()>,
+{
+    let (tx, rx) = channel();
+
+    let mut watcher: RecommendedWatcher =
+        notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+    watcher
+        .watch(source, RecursiveMode::Recursive)
+        .context("Failed to watch app source directory")?;
+
+    println!("=== Watching {} for changes ===", source.display());
+
+    let build_output_dir = source.join("tmp");
+
+    loop {
+        match rx.recv_timeout(DEBOUNCE) {
+            Ok(Ok(event)) if is_relevant(&event, &build_output_dir) => {
+                // Drain any events that arrived during the debounce window so a
+                // burst of saves only triggers a single rebuild.
+                while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+                match rebuild() {
+                    Ok(()) => notify("nixpacks", "Build succeeded"),
+                    Err(err) => notify("nixpacks", &format!("Build failed: {}", err)),
+                }
+
+                // `rebuild` runs synchronously, so events that arrived while it
+                // was in flight are already sitting in the channel. Drop them
+                // now instead of triggering an immediate, redundant rebuild.
+                while rx.try_recv().is_ok() {}
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => eprintln!("Watch error: {}", err),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether an event is outside nixpacks' own `.git`/build-output dirs, and
+/// so should trigger a rebuild.
+///
+/// Only `build_output_dir` itself (`<source>/tmp`, mirroring
+/// `ignore::ALWAYS_EXCLUDED`'s `./tmp` anchor) is ignored — a project with
+/// its own nested `tmp/` directory elsewhere should still trigger rebuilds
+/// when it changes.
+fn is_relevant(event: &notify::Event, build_output_dir: &Path) -> bool {
+    !event.paths.iter().any(|path| {
+        path.components().any(|component| component.as_os_str() == ".git") || path.starts_with(build_output_dir)
+    })
+}
+
+fn notify(summary: &str, body: &str) {
+    if let Err(err) = Notification::new().summary(summary).body(body).show() {
+        eprintln!("Failed to send desktop notification: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::EventKind;
+    use std::path::PathBuf;
+
+    fn event(paths: Vec<PathBuf>) -> notify::Event {
+        notify::Event {
+            kind: EventKind::Any,
+            paths,
+            attrs: Default::default(),
+        }
+    }
+
+    #[test]
+    fn build_output_changes_are_not_relevant() {
+        let output_dir = PathBuf::from("/app/tmp");
+        let evt = event(vec![PathBuf::from("/app/tmp/abc123/Dockerfile")]);
+        assert!(!is_relevant(&evt, &output_dir));
+    }
+
+    #[test]
+    fn nested_project_tmp_dir_is_relevant() {
+        let output_dir = PathBuf::from("/app/tmp");
+        let evt = event(vec![PathBuf::from("/app/sub/tmp/cache.txt")]);
+        assert!(is_relevant(&evt, &output_dir));
+    }
+
+    #[test]
+    fn git_changes_are_not_relevant() {
+        let output_dir = PathBuf::from("/app/tmp");
+        let evt = event(vec![PathBuf::from("/app/.git/HEAD")]);
+        assert!(!is_relevant(&evt, &output_dir));
+    }
+
+    #[test]
+    fn source_changes_are_relevant() {
+        let output_dir = PathBuf::from("/app/tmp");
+        let evt = event(vec![PathBuf::from("/app/src/main.rs")]);
+        assert!(is_relevant(&evt, &output_dir));
+    }
+}
@@ -0,0 +1,20 @@
+use anyhow::Result;
+
+use crate::bb::AppSource;
+
+/// A language/framework builder: detects whether it applies to an app, and
+/// supplies the Nix packages and install/build/start commands needed to
+/// containerize it.
+pub trait Builder {
+    fn name(&self) -> &str;
+
+    fn detect(&self, app: &AppSource) -> Result<bool>;
+
+    fn build_inputs(&self, app: &AppSource) -> String;
+
+    fn install_cmd(&self, app: &AppSource) -> Result<Option<String>>;
+
+    fn suggested_build_cmd(&self, app: &AppSource) -> Result<Option<String>>;
+
+    fn suggested_start_command(&self, app: &AppSource) -> Result<Option<String>>;
+}
@@ -0,0 +1,181 @@
+use std::{path::PathBuf, process::ExitCode};
+
+mod bb;
+mod builders;
+
+use bb::{error::NixpacksError, remote, AppBuilder, AppBuilderOptions, NixPin};
+
+/// Hand-rolled arg parsing is enough for nixpacks' small flag surface; add a
+/// real parser (e.g. clap) if this grows much further.
+struct Args {
+    source: PathBuf,
+    name: Option<String>,
+    build_cmd: Option<String>,
+    start_cmd: Option<String>,
+    pkgs: Vec<String>,
+    engine: Option<String>,
+    entrypoint: bool,
+    push: Option<String>,
+    nixpkgs_pin: Option<NixPin>,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> anyhow::Result<Args> {
+        let mut source = None;
+        let mut name = None;
+        let mut build_cmd = None;
+        let mut start_cmd = None;
+        let mut pkgs = Vec::new();
+        let mut engine = None;
+        let mut entrypoint = false;
+        let mut push = None;
+        let mut nixpkgs_pin = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--name" => name = Some(expect_value(&mut args, "--name")?),
+                "--build-cmd" => build_cmd = Some(expect_value(&mut args, "--build-cmd")?),
+                "--start-cmd" => start_cmd = Some(expect_value(&mut args, "--start-cmd")?),
+                "--pkg" => pkgs.push(expect_value(&mut args, "--pkg")?),
+                "--engine" => engine = Some(expect_value(&mut args, "--engine")?),
+                "--entrypoint" => entrypoint = true,
+                "--push" => push = Some(expect_value(&mut args, "--push")?),
+                "--pin-nixpkgs" => {
+                    nixpkgs_pin = Some(match args.next() {
+                        Some(spec) if !spec.starts_with("--") => parse_pin(&spec)?,
+                        Some(next) => {
+                            // Not a value for us; put it back by re-wrapping isn't
+                            // possible with a plain iterator, so require a value.
+                            anyhow::bail!("--pin-nixpkgs requires a <rev>:<sha256> value, got '{}'", next)
+                        }
+                        None => NixPin::default(),
+                    })
+                }
+                other if source.is_none() => source = Some(PathBuf::from(other)),
+                other => anyhow::bail!("Unrecognized argument '{}'", other),
+            }
+        }
+
+        Ok(Args {
+            source: source.unwrap_or_else(|| PathBuf::from(".")),
+            name,
+            build_cmd,
+            start_cmd,
+            pkgs,
+            engine,
+            entrypoint,
+            push,
+            nixpkgs_pin,
+        })
+    }
+
+    fn into_builder(self) -> anyhow::Result<AppBuilder<'static>> {
+        AppBuilder::new(
+            self.name,
+            self.source,
+            AppBuilderOptions {
+                custom_build_cmd: self.build_cmd,
+                custom_start_cmd: self.start_cmd,
+                pkgs: self.pkgs,
+                engine: self.engine,
+                entrypoint: self.entrypoint,
+                push: self.push,
+                nixpkgs_pin: self.nixpkgs_pin,
+            },
+        )
+    }
+}
+
+/// Pull an optional leading `--engine <name>` off the volume-maintenance
+/// subcommands, returning it alongside whatever positional args remain.
+fn take_engine_flag(args: impl Iterator<Item = String>) -> anyhow::Result<(Option<String>, Vec<String>)> {
+    let mut args = args.peekable();
+    let mut engine = None;
+
+    if args.peek().map(String::as_str) == Some("--engine") {
+        args.next();
+        engine = Some(expect_value(&mut args, "--engine")?);
+    }
+
+    Ok((engine, args.collect()))
+}
+
+fn expect_value(args: &mut impl Iterator<Item = String>, flag: &str) -> anyhow::Result<String> {
+    args.next().ok_or_else(|| anyhow::anyhow!("{} requires a value", flag))
+}
+
+fn parse_pin(spec: &str) -> anyhow::Result<NixPin> {
+    let (rev, sha256) = spec
+        .split_once(':')
+        .ok_or_else(|| anyhow::anyhow!("--pin-nixpkgs expects <rev>:<sha256>, got '{}'", spec))?;
+
+    Ok(NixPin {
+        rev: rev.to_string(),
+        sha256: sha256.to_string(),
+    })
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:#}", err);
+
+            // Forward the failing subprocess's own exit code where we have
+            // one, instead of always exiting 1.
+            let code = err
+                .downcast_ref::<NixpacksError>()
+                .map(NixpacksError::exit_code)
+                .unwrap_or(1);
+
+            ExitCode::from(code as u8)
+        }
+    }
+}
+
+fn run() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+
+    match args.next().as_deref() {
+        Some("watch") => {
+            let mut builder = Args::parse(args)?.into_builder()?;
+            builder.watch(Vec::new())
+        }
+        Some("list-volumes") => {
+            let (engine_name, rest) = take_engine_flag(args)?;
+            anyhow::ensure!(rest.is_empty(), "list-volumes takes no positional arguments");
+            let engine = bb::ContainerEngine::resolve(engine_name.as_deref())?;
+            for name in remote::list_volumes(&engine)? {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        Some("remove-volumes") => {
+            let (engine_name, rest) = take_engine_flag(args)?;
+            let engine = bb::ContainerEngine::resolve(engine_name.as_deref())?;
+            remote::remove_volumes(&engine, &rest)
+        }
+        Some("prune-volumes") => {
+            let (engine_name, rest) = take_engine_flag(args)?;
+            anyhow::ensure!(rest.is_empty(), "prune-volumes takes no positional arguments");
+            let engine = bb::ContainerEngine::resolve(engine_name.as_deref())?;
+            remote::prune_volumes(&engine)
+        }
+        Some("build") => {
+            let mut builder = Args::parse(args)?.into_builder()?;
+            builder.detect(Vec::new())?;
+            builder.build()
+        }
+        // No subcommand: treat the rest of argv as `build`'s arguments.
+        Some(first) => {
+            let mut builder = Args::parse(std::iter::once(first.to_string()).chain(args))?.into_builder()?;
+            builder.detect(Vec::new())?;
+            builder.build()
+        }
+        None => {
+            let mut builder = Args::parse(std::iter::empty())?.into_builder()?;
+            builder.detect(Vec::new())?;
+            builder.build()
+        }
+    }
+}